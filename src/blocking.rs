@@ -0,0 +1,352 @@
+use crate::queue::ReaderWriterQueue;
+use core::cell::UnsafeCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::task::{Context, Poll, Waker};
+use std::sync::Arc;
+use std::thread::{self, Thread};
+
+const WAKER_EMPTY: usize = 0;
+const WAKER_WAITING: usize = 1;
+const WAKER_REGISTERING: usize = 2;
+const WAKER_WAKING: usize = 3;
+
+/// A single-slot, lock-free waker cell: `register` publishes a waker, `wake`
+/// takes whatever waker (if any) was last registered and wakes it.
+struct AtomicWaker {
+    state: AtomicUsize,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+unsafe impl Send for AtomicWaker {}
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+    fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(WAKER_EMPTY),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    fn register(&self, waker: &Waker) {
+        // A poll that found data without ever calling `wake()` leaves this in
+        // WAKER_WAITING, still holding the previous waker; the next `register`
+        // (e.g. after a task migration handed us a different `Waker`) must still
+        // be able to replace it, not just transition out of WAKER_EMPTY.
+        loop {
+            match self.state.compare_exchange(
+                WAKER_EMPTY,
+                WAKER_REGISTERING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(WAKER_WAITING) => {
+                    if self
+                        .state
+                        .compare_exchange(
+                            WAKER_WAITING,
+                            WAKER_REGISTERING,
+                            Ordering::Acquire,
+                            Ordering::Acquire,
+                        )
+                        .is_ok()
+                    {
+                        break;
+                    }
+                }
+                Err(WAKER_WAKING) => {
+                    // A wake is in progress; poll will be called again shortly.
+                    waker.wake_by_ref();
+                    return;
+                }
+                Err(_) => {}
+            }
+        }
+
+        unsafe {
+            *self.waker.get() = Some(waker.clone());
+        }
+        if self
+            .state
+            .compare_exchange(WAKER_REGISTERING, WAKER_WAITING, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            // A `wake` happened while we were registering; it couldn't see the new
+            // waker, so wake it ourselves to avoid a lost wakeup.
+            let woken = unsafe { (*self.waker.get()).take() };
+            self.state.store(WAKER_EMPTY, Ordering::Release);
+            if let Some(w) = woken {
+                w.wake();
+            }
+        }
+    }
+
+    fn wake(&self) {
+        match self.state.swap(WAKER_WAKING, Ordering::AcqRel) {
+            WAKER_WAITING => {
+                let woken = unsafe { (*self.waker.get()).take() };
+                self.state.store(WAKER_EMPTY, Ordering::Release);
+                if let Some(w) = woken {
+                    w.wake();
+                }
+            }
+            WAKER_EMPTY => {
+                self.state.store(WAKER_EMPTY, Ordering::Release);
+            }
+            _ => {
+                // Registering or already waking: nothing parked to wake.
+            }
+        }
+    }
+}
+
+/// An SPSC queue that lets its single consumer `.await` the next element and its
+/// single producer `.await` room, instead of spinning on [`ReaderWriterQueue::try_dequeue`].
+///
+/// Wrap in an `Arc` and call [`split`](Self::split) once to get a
+/// [`BlockingProducer`]/[`BlockingConsumer`] pair; taking either half twice panics,
+/// since the single-producer/single-consumer contract of [`ReaderWriterQueue`]
+/// is enforced here at runtime rather than through `&mut self`.
+pub struct BlockingReaderWriterQueue<T, const MAX_BLOCK_SIZE: usize = 512> {
+    queue: UnsafeCell<ReaderWriterQueue<T, MAX_BLOCK_SIZE>>,
+    consumer_waker: AtomicWaker,
+    producer_waker: AtomicWaker,
+    closed: AtomicBool,
+    producer_taken: AtomicBool,
+    consumer_taken: AtomicBool,
+}
+
+unsafe impl<T: Send, const MAX_BLOCK_SIZE: usize> Send for BlockingReaderWriterQueue<T, MAX_BLOCK_SIZE> {}
+unsafe impl<T: Send, const MAX_BLOCK_SIZE: usize> Sync for BlockingReaderWriterQueue<T, MAX_BLOCK_SIZE> {}
+
+impl<T, const MAX_BLOCK_SIZE: usize> BlockingReaderWriterQueue<T, MAX_BLOCK_SIZE> {
+    pub fn new() -> Self {
+        Self::with_size(15)
+    }
+
+    pub fn with_size(size: usize) -> Self {
+        Self {
+            queue: UnsafeCell::new(ReaderWriterQueue::with_size(size)),
+            consumer_waker: AtomicWaker::new(),
+            producer_waker: AtomicWaker::new(),
+            closed: AtomicBool::new(false),
+            producer_taken: AtomicBool::new(false),
+            consumer_taken: AtomicBool::new(false),
+        }
+    }
+
+    /// Takes the producer half. Panics if it was already taken: the underlying
+    /// `queue_mut()` hands out an aliased `&mut ReaderWriterQueue`, so at most one
+    /// producer and one consumer may ever exist at a time.
+    pub fn take_producer(self: &Arc<Self>) -> BlockingProducer<T, MAX_BLOCK_SIZE> {
+        assert!(
+            !self.producer_taken.swap(true, Ordering::AcqRel),
+            "BlockingReaderWriterQueue: producer half already taken"
+        );
+        BlockingProducer { queue: self.clone() }
+    }
+
+    /// Takes the consumer half. Panics if it was already taken; see [`take_producer`](Self::take_producer).
+    pub fn take_consumer(self: &Arc<Self>) -> BlockingConsumer<T, MAX_BLOCK_SIZE> {
+        assert!(
+            !self.consumer_taken.swap(true, Ordering::AcqRel),
+            "BlockingReaderWriterQueue: consumer half already taken"
+        );
+        BlockingConsumer { queue: self.clone() }
+    }
+
+    /// Takes both halves at once, for the common case of handing one to a
+    /// producer task and the other to a consumer task.
+    pub fn split(self: &Arc<Self>) -> (BlockingProducer<T, MAX_BLOCK_SIZE>, BlockingConsumer<T, MAX_BLOCK_SIZE>) {
+        (self.take_producer(), self.take_consumer())
+    }
+
+    /// Safety: only ever called from the single producer or the single consumer,
+    /// enforced at runtime by `take_producer`/`take_consumer` each only ever
+    /// handing out one handle; `try_dequeue` only touches the front block and
+    /// `enqueue` only touches the tail block, so the two never alias.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn queue_mut(&self) -> &mut ReaderWriterQueue<T, MAX_BLOCK_SIZE> {
+        &mut *self.queue.get()
+    }
+
+    /// Closes the queue: wakes both sides and makes pending/future operations
+    /// return `None`/an error instead of waiting forever.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.consumer_waker.wake();
+        self.producer_waker.wake();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    fn poll_dequeue(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        // Register before checking so a concurrent enqueue can't be missed between
+        // the check and the registration (the classic lost-wakeup race).
+        self.consumer_waker.register(cx.waker());
+        // Read `closed` before trying the queue, so an element enqueued just
+        // before `close()` is still returned rather than dropped.
+        let closed = self.is_closed();
+        if let Some(value) = unsafe { self.queue_mut() }.try_dequeue() {
+            self.producer_waker.wake();
+            return Poll::Ready(Some(value));
+        }
+        if closed {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    }
+}
+
+/// The enqueue half of a [`BlockingReaderWriterQueue`], obtained from
+/// [`take_producer`](BlockingReaderWriterQueue::take_producer) or [`split`](BlockingReaderWriterQueue::split).
+pub struct BlockingProducer<T, const MAX_BLOCK_SIZE: usize = 512> {
+    queue: Arc<BlockingReaderWriterQueue<T, MAX_BLOCK_SIZE>>,
+}
+
+impl<T, const MAX_BLOCK_SIZE: usize> BlockingProducer<T, MAX_BLOCK_SIZE> {
+    pub fn close(&self) {
+        self.queue.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+
+    /// Enqueues `value`. The underlying queue grows on demand and never runs out
+    /// of room, so the returned future completes on its first poll, with
+    /// `Err(value)` if the queue is closed; it never registers a waker or parks.
+    pub fn enqueue(&self, value: T) -> EnqueueFuture<'_, T, MAX_BLOCK_SIZE> {
+        EnqueueFuture {
+            queue: &self.queue,
+            value: Some(value),
+        }
+    }
+
+    /// Blocking equivalent of [`enqueue`](Self::enqueue), for non-async producers.
+    pub fn wait_enqueue(&self, value: T) -> Result<(), T> {
+        if self.queue.is_closed() {
+            return Err(value);
+        }
+        unsafe { self.queue.queue_mut() }.enqueue(value);
+        self.queue.consumer_waker.wake();
+        Ok(())
+    }
+}
+
+/// The dequeue half of a [`BlockingReaderWriterQueue`], obtained from
+/// [`take_consumer`](BlockingReaderWriterQueue::take_consumer) or [`split`](BlockingReaderWriterQueue::split).
+pub struct BlockingConsumer<T, const MAX_BLOCK_SIZE: usize = 512> {
+    queue: Arc<BlockingReaderWriterQueue<T, MAX_BLOCK_SIZE>>,
+}
+
+impl<T, const MAX_BLOCK_SIZE: usize> BlockingConsumer<T, MAX_BLOCK_SIZE> {
+    pub fn close(&self) {
+        self.queue.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+
+    /// Awaits the next element, or `None` once the queue has been [`close`](Self::close)d
+    /// and drained.
+    pub fn dequeue(&self) -> DequeueFuture<'_, T, MAX_BLOCK_SIZE> {
+        DequeueFuture { queue: &self.queue }
+    }
+
+    /// Blocking equivalent of [`dequeue`](Self::dequeue), for non-async consumers.
+    pub fn wait_dequeue(&self) -> Option<T> {
+        loop {
+            // As in `poll_dequeue`, read `closed` before each try so the queue
+            // is drained before `None` is returned.
+            let closed = self.queue.is_closed();
+            if let Some(value) = unsafe { self.queue.queue_mut() }.try_dequeue() {
+                self.queue.producer_waker.wake();
+                return Some(value);
+            }
+            if closed {
+                return None;
+            }
+            self.queue.consumer_waker.register(&thread_waker());
+            let closed = self.queue.is_closed();
+            if let Some(value) = unsafe { self.queue.queue_mut() }.try_dequeue() {
+                self.queue.producer_waker.wake();
+                return Some(value);
+            }
+            if closed {
+                return None;
+            }
+            thread::park();
+        }
+    }
+}
+
+pub struct DequeueFuture<'a, T, const MAX_BLOCK_SIZE: usize> {
+    queue: &'a BlockingReaderWriterQueue<T, MAX_BLOCK_SIZE>,
+}
+
+impl<'a, T, const MAX_BLOCK_SIZE: usize> Future for DequeueFuture<'a, T, MAX_BLOCK_SIZE> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.queue.poll_dequeue(cx)
+    }
+}
+
+pub struct EnqueueFuture<'a, T, const MAX_BLOCK_SIZE: usize> {
+    queue: &'a BlockingReaderWriterQueue<T, MAX_BLOCK_SIZE>,
+    value: Option<T>,
+}
+
+impl<'a, T, const MAX_BLOCK_SIZE: usize> Future for EnqueueFuture<'a, T, MAX_BLOCK_SIZE> {
+    type Output = Result<(), T>;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Never self-referential, so it's sound to get a plain `&mut` out of the
+        // pin without requiring `T: Unpin`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let value = this.value.take().expect("EnqueueFuture polled after completion");
+        if this.queue.is_closed() {
+            return Poll::Ready(Err(value));
+        }
+        unsafe { this.queue.queue_mut() }.enqueue(value);
+        this.queue.consumer_waker.wake();
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Builds a [`Waker`] that unparks the calling thread, for the synchronous
+/// `wait_dequeue` path to share the same `AtomicWaker` slot as the async path.
+fn thread_waker() -> Waker {
+    fn clone(thread: *const ()) -> std::task::RawWaker {
+        let thread = unsafe { Arc::from_raw(thread as *const Thread) };
+        let cloned = thread.clone();
+        core::mem::forget(thread);
+        std::task::RawWaker::new(Arc::into_raw(cloned) as *const (), &VTABLE)
+    }
+    fn wake(thread: *const ()) {
+        let thread = unsafe { Arc::from_raw(thread as *const Thread) };
+        thread.unpark();
+    }
+    fn wake_by_ref(thread: *const ()) {
+        let thread = unsafe { Arc::from_raw(thread as *const Thread) };
+        thread.unpark();
+        core::mem::forget(thread);
+    }
+    fn drop_waker(thread: *const ()) {
+        unsafe { drop(Arc::from_raw(thread as *const Thread)) };
+    }
+
+    static VTABLE: std::task::RawWakerVTable =
+        std::task::RawWakerVTable::new(clone, wake, wake_by_ref, drop_waker);
+
+    let thread = Arc::new(thread::current());
+    let raw = std::task::RawWaker::new(Arc::into_raw(thread) as *const (), &VTABLE);
+    unsafe { Waker::from_raw(raw) }
+}
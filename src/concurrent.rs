@@ -0,0 +1,130 @@
+use core::cell::UnsafeCell;
+use core::mem::{size_of, MaybeUninit};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+const CACHE_LINE_SIZE: usize = 64;
+
+struct Slot<T> {
+    /// Which lap this slot is expecting next: `i` while empty and awaiting its
+    /// first push, `tail + 1` once filled, `head + one_lap` once drained again.
+    stamp: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A bounded, lock-free multi-producer/multi-consumer queue implementing
+/// Dmitry Vyukov's stamped-slot ring buffer. Producers and consumers race only
+/// on the slot's own stamp, never on a shared lock, so pushes and pops to
+/// different slots proceed fully in parallel.
+pub struct ConcurrentQueue<T> {
+    buffer: Box<[Slot<T>]>,
+    cap: usize,
+    one_lap: usize,
+    /// (Atomic) Elements are dequeued from this position.
+    head: AtomicUsize,
+    cacheline_filler0: [u8; CACHE_LINE_SIZE - size_of::<AtomicUsize>()],
+    /// (Atomic) Elements are enqueued to this position.
+    tail: AtomicUsize,
+    cacheline_filler1: [u8; CACHE_LINE_SIZE - size_of::<AtomicUsize>()],
+}
+
+unsafe impl<T: Send> Send for ConcurrentQueue<T> {}
+unsafe impl<T: Send> Sync for ConcurrentQueue<T> {}
+
+impl<T> ConcurrentQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be non-zero");
+        let one_lap = (capacity + 1).next_power_of_two();
+        let buffer = (0..capacity)
+            .map(|i| Slot {
+                stamp: AtomicUsize::new(i),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self {
+            buffer,
+            cap: capacity,
+            one_lap,
+            head: AtomicUsize::new(0),
+            cacheline_filler0: [0; CACHE_LINE_SIZE - size_of::<AtomicUsize>()],
+            tail: AtomicUsize::new(0),
+            cacheline_filler1: [0; CACHE_LINE_SIZE - size_of::<AtomicUsize>()],
+        }
+    }
+
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        loop {
+            let index = tail & (self.one_lap - 1);
+            let lap = tail & !(self.one_lap - 1);
+            let slot = &self.buffer[index];
+            let stamp = slot.stamp.load(Ordering::Acquire);
+
+            if tail == stamp {
+                // The slot is ready for this lap: try to claim it.
+                let new_tail = if index + 1 < self.cap {
+                    tail + 1
+                } else {
+                    lap.wrapping_add(self.one_lap)
+                };
+                match self
+                    .tail
+                    .compare_exchange_weak(tail, new_tail, Ordering::SeqCst, Ordering::Relaxed)
+                {
+                    Ok(_) => {
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.stamp.store(tail + 1, Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(t) => tail = t,
+                }
+            } else if stamp.wrapping_add(self.one_lap) == tail + 1 {
+                // The slot still holds the previous lap's value: queue is full.
+                return Err(value);
+            } else {
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let index = head & (self.one_lap - 1);
+            let lap = head & !(self.one_lap - 1);
+            let slot = &self.buffer[index];
+            let stamp = slot.stamp.load(Ordering::Acquire);
+
+            if head + 1 == stamp {
+                // The slot holds this lap's value: try to claim it.
+                let new_head = if index + 1 < self.cap {
+                    head + 1
+                } else {
+                    lap.wrapping_add(self.one_lap)
+                };
+                match self
+                    .head
+                    .compare_exchange_weak(head, new_head, Ordering::SeqCst, Ordering::Relaxed)
+                {
+                    Ok(_) => {
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        slot.stamp.store(head.wrapping_add(self.one_lap), Ordering::Release);
+                        return Some(value);
+                    }
+                    Err(h) => head = h,
+                }
+            } else if stamp == head {
+                // The slot is still empty: queue is empty.
+                return None;
+            } else {
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Drop for ConcurrentQueue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
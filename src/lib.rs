@@ -0,0 +1,14 @@
+pub mod queue;
+pub use queue::ReaderWriterQueue;
+
+pub mod blocking;
+pub use blocking::{BlockingConsumer, BlockingProducer, BlockingReaderWriterQueue};
+
+pub mod static_queue;
+pub use static_queue::StaticReaderWriterQueue;
+
+pub mod concurrent;
+pub use concurrent::ConcurrentQueue;
+
+pub mod seg_queue;
+pub use seg_queue::SegQueue;
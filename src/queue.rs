@@ -1,6 +1,6 @@
 use core::marker::PhantomData;
 use core::mem::{self, align_of, size_of};
-use core::ptr::{drop_in_place, null_mut};
+use core::ptr::{self, copy_nonoverlapping, drop_in_place, null_mut};
 use core::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
 extern crate alloc;
 
@@ -13,6 +13,10 @@ pub struct ReaderWriterQueue<T, const MAX_BLOCK_SIZE: usize = 512> {
     /// (Atomic) Elements are enqueued to this block
     tail_block: AtomicPtr<Block>,
     largest_block_size: usize,
+    /// Set only by [`ring`](Self::ring): forbids the growth paths (`enqueue`,
+    /// `enqueue_bulk`) from silently allocating past the fixed capacity instead
+    /// of evicting like [`push_overwrite`](Self::push_overwrite) does.
+    ring_mode: bool,
     #[cfg(debug_assertions)]
     enqueuing: AtomicBool,
     #[cfg(debug_assertions)]
@@ -63,6 +67,7 @@ impl<T, const MAX_BLOCK_SIZE: usize> ReaderWriterQueue<T, MAX_BLOCK_SIZE> {
             tail_block: AtomicPtr::new(first_block),
             cacheline_filler: [0; CACHE_LINE_SIZE - size_of::<AtomicPtr<Block>>()],
             largest_block_size,
+            ring_mode: false,
             #[cfg(debug_assertions)]
             enqueuing: AtomicBool::new(false),
             #[cfg(debug_assertions)]
@@ -71,12 +76,88 @@ impl<T, const MAX_BLOCK_SIZE: usize> ReaderWriterQueue<T, MAX_BLOCK_SIZE> {
         }
     }
 
+    /// Builds a fixed-capacity ring: a single block sized for `capacity` elements
+    /// that never grows. Meant to be driven with [`push_overwrite`](Self::push_overwrite)
+    /// so a slow consumer never blocks the producer; `enqueue`/`enqueue_bulk` panic
+    /// on a ring queue instead of silently allocating past its fixed capacity.
+    pub fn ring(capacity: usize) -> Self {
+        let block_size = ceilToPow2(capacity + 1).max(2);
+        let block = Self::make_block(block_size);
+        unsafe {
+            (*block).next = AtomicPtr::new(block);
+            // block_size is rounded up to a power of 2 for the size_mask trick,
+            // which can leave slack beyond `capacity` (e.g. capacity=4 rounds
+            // to an 8-slot block); push_overwrite must evict at the requested
+            // capacity, not whenever the padded block happens to fill up.
+            (*block).ring_capacity = capacity;
+        }
+        fence(Ordering::SeqCst);
+        Self {
+            front_block: AtomicPtr::new(block),
+            tail_block: AtomicPtr::new(block),
+            cacheline_filler: [0; CACHE_LINE_SIZE - size_of::<AtomicPtr<Block>>()],
+            largest_block_size: block_size,
+            ring_mode: true,
+            #[cfg(debug_assertions)]
+            enqueuing: AtomicBool::new(false),
+            #[cfg(debug_assertions)]
+            dequeuing: AtomicBool::new(false),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Pushes `value`, and if the ring is full, evicts and returns the oldest
+    /// element instead of allocating a new block. "Full" means `ring_capacity`
+    /// elements are already queued, not merely that the (possibly larger,
+    /// power-of-2-padded) backing block has run out of slots.
+    pub fn push_overwrite(&mut self, value: T) -> Option<T> {
+        let tail_block = self.tail_block.load(Ordering::Relaxed);
+        let size_mask = unsafe { (*tail_block).size_mask };
+        let ring_capacity = unsafe { (*tail_block).ring_capacity };
+        let block_tail = unsafe { (*tail_block).tail.load(Ordering::Relaxed) };
+        let local_front = unsafe {
+            (*tail_block).local_front = (*tail_block).front.load(Ordering::Acquire);
+            (*tail_block).local_front
+        };
+        let next_tail = (block_tail + 1) & size_mask;
+        let filled = (block_tail.wrapping_sub(local_front)) & size_mask;
+
+        let evicted = if filled >= ring_capacity {
+            let oldest = unsafe { (*tail_block).data.add(local_front * size_of::<T>()) as *mut T };
+            let displaced = unsafe { core::ptr::read(oldest) };
+            let new_front = (local_front + 1) & size_mask;
+            unsafe {
+                (*tail_block).front.store(new_front, Ordering::Release);
+                // try_dequeue's `local_tail` cache assumes `front` only ever
+                // moves while the consumer is the one moving it, starting from
+                // 0 on a fresh block. Evicting here moves `front` out from
+                // under that assumption, so without this the consumer's stale
+                // cached tail (still 0) would never look like it needs
+                // refreshing and it would read past the real tail. Since every
+                // queue method takes `&mut self`, there's no concurrent
+                // consumer to race with.
+                (*tail_block).local_tail = next_tail;
+            }
+            Some(displaced)
+        } else {
+            None
+        };
+
+        unsafe {
+            let slot = (*tail_block).data.add(block_tail * size_of::<T>()) as *mut T;
+            core::ptr::write(slot, value);
+            (*tail_block).tail.store(next_tail, Ordering::Release);
+        }
+        evicted
+    }
+
     pub fn from_other(other: &mut Self) -> Self {
         let item = Self {
             front_block: AtomicPtr::new(other.front_block.load(Ordering::Relaxed)),
             tail_block: AtomicPtr::new(other.tail_block.load(Ordering::Relaxed)),
             cacheline_filler: [0; CACHE_LINE_SIZE - size_of::<AtomicPtr<Block>>()],
             largest_block_size: other.largest_block_size,
+            ring_mode: other.ring_mode,
             #[cfg(debug_assertions)]
             enqueuing: AtomicBool::new(false),
             #[cfg(debug_assertions)]
@@ -93,41 +174,193 @@ impl<T, const MAX_BLOCK_SIZE: usize> ReaderWriterQueue<T, MAX_BLOCK_SIZE> {
         item
     }
 
+    /// Enqueues a single element, growing the ring by splicing in a fresh block
+    /// when the tail block is full. `enqueue_bulk` grows the ring the same way
+    /// for a whole slice at once, with its own copy loop rather than by calling this.
+    pub fn enqueue(&mut self, value: T) {
+        assert!(
+            !self.ring_mode,
+            "enqueue() would grow a ring queue past its fixed capacity; use push_overwrite() instead"
+        );
+        loop {
+            let tail_block = self.tail_block.load(Ordering::Relaxed);
+            let size_mask = unsafe { (*tail_block).size_mask };
+            let block_tail = unsafe { (*tail_block).tail.load(Ordering::Relaxed) };
+            let local_front = unsafe {
+                (*tail_block).local_front = (*tail_block).front.load(Ordering::Acquire);
+                (*tail_block).local_front
+            };
+            let next_tail = (block_tail + 1) & size_mask;
+            if next_tail == local_front {
+                // Tail block is full; splice a fresh block into the ring right after it.
+                let new_block = Self::make_block(self.largest_block_size);
+                unsafe {
+                    let next = (*tail_block).next.load(Ordering::Relaxed);
+                    (*new_block).next = AtomicPtr::new(next);
+                    (*tail_block).next.store(new_block, Ordering::Release);
+                }
+                self.tail_block.store(new_block, Ordering::Release);
+                continue;
+            }
+            unsafe {
+                let slot = (*tail_block).data.add(block_tail * size_of::<T>()) as *mut T;
+                ptr::write(slot, value);
+                (*tail_block).tail.store(next_tail, Ordering::Release);
+            }
+            return;
+        }
+    }
+
+    /// Dequeues a single element, hopping to the next block in the ring once
+    /// the front block runs dry. Drained blocks are left in the ring (not
+    /// freed here): the ring is circular, and freeing one mid-walk would leave
+    /// whichever block still closes the cycle back to it pointing at freed
+    /// memory. `Drop` is the only place blocks are freed, once each, by
+    /// walking the whole ring in one pass after both ends are done with it.
     pub fn try_dequeue(&mut self) -> Option<T> {
-        let front_block = self.front_block.load(Ordering::Relaxed);
-        let block_tail = unsafe { (*front_block).local_tail };
-        let block_front = unsafe { (*front_block).front.load(Ordering::Relaxed) };
-        if block_front != block_tail
-            || block_front
-                != unsafe {
-                    (*front_block).local_tail = (*front_block).tail.load(Ordering::Relaxed);
+        loop {
+            let front_block = self.front_block.load(Ordering::Relaxed);
+            let block_front = unsafe { (*front_block).front.load(Ordering::Relaxed) };
+            let mut block_tail = unsafe { (*front_block).local_tail };
+            if block_front == block_tail {
+                block_tail = unsafe {
+                    (*front_block).local_tail = (*front_block).tail.load(Ordering::Acquire);
                     (*front_block).local_tail
+                };
+            }
+            if block_front != block_tail {
+                fence(Ordering::Acquire);
+                let size_mask = unsafe { (*front_block).size_mask };
+                let element =
+                    unsafe { (*front_block).data.add(block_front * size_of::<T>()) as *mut T };
+                let value = unsafe { ptr::read(element) };
+                unsafe {
+                    (*front_block)
+                        .front
+                        .store((block_front + 1) & size_mask, Ordering::Release)
+                };
+                return Some(value);
+            } else if front_block != self.tail_block.load(Ordering::Relaxed) {
+                // Front block is fully drained; hop to the next one in the
+                // ring. Left allocated for `Drop` to free; see the doc comment
+                // above.
+                let next_block = unsafe { (*front_block).next.load(Ordering::Relaxed) };
+                self.front_block.store(next_block, Ordering::Release);
+            } else {
+                return None;
+            }
+        }
+    }
+
+    /// Enqueues every element of `src` in one go. Each contiguous run that fits
+    /// inside the tail block's array without wrapping past `size_mask` is moved
+    /// with a single `ptr::copy_nonoverlapping`, and the block's `tail` is
+    /// published once per run rather than once per element. When the tail block
+    /// fills up, a fresh block is spliced into the ring, exactly as repeated
+    /// single-element enqueues would do.
+    pub fn enqueue_bulk(&mut self, src: &[T])
+    where
+        T: Copy,
+    {
+        assert!(
+            !self.ring_mode,
+            "enqueue_bulk() would grow a ring queue past its fixed capacity; use push_overwrite() instead"
+        );
+        let mut remaining = src;
+        while !remaining.is_empty() {
+            let tail_block = self.tail_block.load(Ordering::Relaxed);
+            let size_mask = unsafe { (*tail_block).size_mask };
+            let block_size = size_mask + 1;
+            let block_tail = unsafe { (*tail_block).tail.load(Ordering::Relaxed) };
+            let local_front = unsafe {
+                (*tail_block).local_front = (*tail_block).front.load(Ordering::Acquire);
+                (*tail_block).local_front
+            };
+            // One slot is always left empty so `front == tail` unambiguously means "empty".
+            let free = (local_front.wrapping_sub(block_tail).wrapping_sub(1)) & size_mask;
+            let run_to_wrap = block_size - block_tail;
+            let run = remaining.len().min(free).min(run_to_wrap);
+            if run == 0 {
+                // Tail block is full; splice a fresh block into the ring right after it.
+                let new_block = Self::make_block(self.largest_block_size);
+                unsafe {
+                    let next = (*tail_block).next.load(Ordering::Relaxed);
+                    (*new_block).next = AtomicPtr::new(next);
+                    (*tail_block).next.store(new_block, Ordering::Release);
                 }
-        {
+                self.tail_block.store(new_block, Ordering::Release);
+                continue;
+            }
+            unsafe {
+                let dst = (*tail_block).data.add(block_tail * size_of::<T>()) as *mut T;
+                copy_nonoverlapping(remaining.as_ptr(), dst, run);
+                (*tail_block).tail.store((block_tail + run) & size_mask, Ordering::Release);
+            }
+            remaining = &remaining[run..];
+        }
+    }
+
+    /// Dequeues as many elements as will fit into `dst`, returning how many were
+    /// moved. As with `enqueue_bulk`, each contiguous run within a block is moved
+    /// with one `ptr::copy_nonoverlapping` and the block's `front` is published
+    /// once per run. The ring advances to the next block as soon as the current
+    /// one is fully drained; drained blocks are left in the ring rather than
+    /// freed here (see `try_dequeue`'s doc comment for why).
+    pub fn try_dequeue_bulk(&mut self, dst: &mut [T]) -> usize {
+        let mut filled = 0usize;
+        while filled < dst.len() {
+            let front_block = self.front_block.load(Ordering::Relaxed);
+            let block_front = unsafe { (*front_block).front.load(Ordering::Relaxed) };
+            let mut block_tail = unsafe { (*front_block).local_tail };
+            if block_front == block_tail {
+                block_tail = unsafe {
+                    (*front_block).local_tail = (*front_block).tail.load(Ordering::Acquire);
+                    (*front_block).local_tail
+                };
+                if block_front == block_tail {
+                    if front_block == self.tail_block.load(Ordering::Relaxed) {
+                        break; // Queue is empty.
+                    }
+                    // This block is fully drained; advance to the next one in
+                    // the ring. Left allocated for `Drop` to free.
+                    let next_block = unsafe { (*front_block).next.load(Ordering::Relaxed) };
+                    self.front_block.store(next_block, Ordering::Release);
+                    continue;
+                }
+            }
             fence(Ordering::Acquire);
+            let size_mask = unsafe { (*front_block).size_mask };
+            let available = (block_tail.wrapping_sub(block_front)) & size_mask;
+            let run_to_wrap = size_mask + 1 - block_front;
+            let run = (dst.len() - filled).min(available).min(run_to_wrap);
+            unsafe {
+                let src = (*front_block).data.add(block_front * size_of::<T>()) as *const T;
+                copy_nonoverlapping(src, dst[filled..].as_mut_ptr(), run);
+                (*front_block).front.store((block_front + run) & size_mask, Ordering::Release);
+            }
+            filled += run;
+        }
+        filled
+    }
 
-            let element =
-                unsafe { (*front_block).data.add(block_front * size_of::<T>()) as *mut T };
-            // return Some(unsafe { *element });
-        } else if front_block != self.tail_block.load(Ordering::Relaxed) {
-            let element =
-                unsafe { (*front_block).data.add(block_front * size_of::<T>()) as *mut T };
-            // return Some(unsafe { *element });
-        } else {
-            return None;
+    fn free_block(block: *mut Block) {
+        unsafe {
+            let raw_this = (*block).raw_this;
+            let raw_size = (*block).raw_size;
+            drop(Box::from_raw(block));
+            alloc::alloc::dealloc(raw_this, core::alloc::Layout::array::<u8>(raw_size).unwrap());
         }
-        None
     }
 
     fn make_block(capacity: usize) -> *mut Block {
         let mut size = size_of::<Block>() + align_of::<Block>() - 1;
-        size += size_of::<T>() + align_of::<T>() - 1;
+        size += size_of::<T>() * capacity + align_of::<T>() - 1;
         let new_block_raw =
             unsafe { alloc::alloc::alloc(core::alloc::Layout::array::<u8>(size).unwrap()) };
         let new_block_aligned = unsafe { align_for::<Block>(new_block_raw) };
         let new_block_data =
-            unsafe { align_for::<Block>(new_block_aligned.add(size_of::<Block>())) };
-        Box::new(Block::new(capacity, new_block_raw, new_block_data)).as_mut()
+            unsafe { align_for::<T>(new_block_aligned.add(size_of::<Block>())) };
+        Box::into_raw(Box::new(Block::new(capacity, new_block_raw, size, new_block_data)))
     }
 }
 
@@ -146,9 +379,7 @@ impl<T, const MAX_BLOCK_SIZE: usize> Drop for ReaderWriterQueue<T, MAX_BLOCK_SIZ
                 unsafe { drop_in_place(element) };
                 i = (i + 1) & unsafe { (*block).size_mask };
             }
-            let raw_block = unsafe { (*block).raw_this };
-            drop(block);
-            drop(raw_block);
+            Self::free_block(block);
             block = next_block;
             if block == front_block {
                 break;
@@ -169,12 +400,19 @@ struct Block {
     next: AtomicPtr<Block>,
     data: *mut u8,
     size_mask: usize,
+    /// Logical capacity for [`ring`](ReaderWriterQueue::ring) blocks (defaults
+    /// to `size_mask`, i.e. no tighter than the block itself, for non-ring
+    /// blocks where it's unused). See `push_overwrite`.
+    ring_capacity: usize,
 
     pub raw_this: *mut u8,
+    /// Size (in bytes) of the `raw_this` allocation, needed to rebuild the
+    /// `Layout` it was allocated with when freeing it.
+    pub raw_size: usize,
 }
 
 impl Block {
-    pub fn new(size: usize, raw_this: *mut u8, data: *mut u8) -> Block {
+    pub fn new(size: usize, raw_this: *mut u8, raw_size: usize, data: *mut u8) -> Block {
         Block {
             front: AtomicUsize::new(0),
             local_tail: 0,
@@ -185,7 +423,9 @@ impl Block {
             next: AtomicPtr::new(null_mut()),
             data,
             size_mask: size - 1,
+            ring_capacity: size - 1,
             raw_this,
+            raw_size,
         }
     }
 }
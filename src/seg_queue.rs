@@ -0,0 +1,202 @@
+use core::cell::UnsafeCell;
+use core::mem::{size_of, MaybeUninit};
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+const CACHE_LINE_SIZE: usize = 64;
+const BLOCK_CAP: usize = 32;
+
+const WRITE: usize = 0b001;
+const READ: usize = 0b010;
+const DESTROY: usize = 0b100;
+
+struct Slot<T> {
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// `WRITE` once a producer has stored a value, `READ` once a consumer has
+    /// taken it, `DESTROY` won (via `fetch_or`) by whichever side frees the block.
+    state: AtomicUsize,
+}
+
+struct Block<T> {
+    slots: [Slot<T>; BLOCK_CAP],
+    next: AtomicPtr<Block<T>>,
+}
+
+impl<T> Block<T> {
+    fn alloc() -> *mut Block<T> {
+        Box::into_raw(Box::new(Block {
+            slots: core::array::from_fn(|_| Slot {
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                state: AtomicUsize::new(0),
+            }),
+            next: AtomicPtr::new(null_mut()),
+        }))
+    }
+
+    unsafe fn dealloc(block: *mut Block<T>) {
+        drop(Box::from_raw(block));
+    }
+}
+
+/// An unbounded, segmented MPMC queue. Producers link fresh blocks on demand;
+/// a block is freed once every slot in it has been read, via the per-slot
+/// `DESTROY` hand-off.
+pub struct SegQueue<T> {
+    head_block: AtomicPtr<Block<T>>,
+    head_index: AtomicUsize,
+    cacheline_filler0: [u8; CACHE_LINE_SIZE - 2 * size_of::<usize>()],
+    tail_block: AtomicPtr<Block<T>>,
+    tail_index: AtomicUsize,
+    cacheline_filler1: [u8; CACHE_LINE_SIZE - 2 * size_of::<usize>()],
+}
+
+unsafe impl<T: Send> Send for SegQueue<T> {}
+unsafe impl<T: Send> Sync for SegQueue<T> {}
+
+impl<T> SegQueue<T> {
+    pub fn new() -> Self {
+        let block = Block::<T>::alloc();
+        Self {
+            head_block: AtomicPtr::new(block),
+            head_index: AtomicUsize::new(0),
+            cacheline_filler0: [0; CACHE_LINE_SIZE - 2 * size_of::<usize>()],
+            tail_block: AtomicPtr::new(block),
+            tail_index: AtomicUsize::new(0),
+            cacheline_filler1: [0; CACHE_LINE_SIZE - 2 * size_of::<usize>()],
+        }
+    }
+
+    pub fn push(&self, value: T) {
+        loop {
+            let block = self.tail_block.load(Ordering::Acquire);
+            let index = self.tail_index.load(Ordering::Acquire);
+            if index >= BLOCK_CAP {
+                core::hint::spin_loop();
+                continue;
+            }
+            if self
+                .tail_index
+                .compare_exchange_weak(index, index + 1, Ordering::AcqRel, Ordering::Relaxed)
+                .is_err()
+            {
+                continue;
+            }
+
+            let block_ref = unsafe { &*block };
+            let slot = &block_ref.slots[index];
+            unsafe { (*slot.value.get()).write(value) };
+
+            if index == BLOCK_CAP - 1 {
+                // We're filling the last slot: link (or adopt) the next block and
+                // hand the ring over to it *before* marking this slot WRITE. A
+                // consumer can only observe WRITE on this slot (Acquire) after
+                // this fetch_or's Release, so by construction it can never see
+                // `next` still null and free this block out from under us.
+                let next = block_ref.next.load(Ordering::Acquire);
+                let next = if next.is_null() {
+                    let new_block = Block::<T>::alloc();
+                    match block_ref.next.compare_exchange(
+                        null_mut(),
+                        new_block,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => new_block,
+                        Err(actual) => {
+                            unsafe { Block::<T>::dealloc(new_block) };
+                            actual
+                        }
+                    }
+                } else {
+                    next
+                };
+                self.tail_block.store(next, Ordering::Release);
+                self.tail_index.store(0, Ordering::Release);
+            }
+
+            slot.state.fetch_or(WRITE, Ordering::Release);
+            return;
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        loop {
+            let block = self.head_block.load(Ordering::Acquire);
+            let index = self.head_index.load(Ordering::Acquire);
+            if index >= BLOCK_CAP {
+                core::hint::spin_loop();
+                continue;
+            }
+            // The slot we'd claim must already be filled, or the queue (at least
+            // up to here) is empty.
+            let block_ref = unsafe { &*block };
+            let slot = &block_ref.slots[index];
+            if slot.state.load(Ordering::Acquire) & WRITE == 0 {
+                if block != self.tail_block.load(Ordering::Acquire) || index != self.tail_index.load(Ordering::Acquire) {
+                    // A producer is still mid-write to this slot; retry.
+                    core::hint::spin_loop();
+                    continue;
+                }
+                return None;
+            }
+            if self
+                .head_index
+                .compare_exchange_weak(index, index + 1, Ordering::AcqRel, Ordering::Relaxed)
+                .is_err()
+            {
+                continue;
+            }
+
+            let value = unsafe { (*slot.value.get()).assume_init_read() };
+            let prev_state = slot.state.fetch_or(READ, Ordering::AcqRel);
+            debug_assert_ne!(prev_state & READ, READ, "slot read twice");
+
+            if index == BLOCK_CAP - 1 {
+                // Last slot in the block: whoever wins the DESTROY bit on it frees
+                // the whole block's backing memory once every other slot has been
+                // observed as read.
+                if slot.state.fetch_or(DESTROY, Ordering::AcqRel) & DESTROY == 0 {
+                    while block_ref.slots[..BLOCK_CAP - 1]
+                        .iter()
+                        .any(|s| s.state.load(Ordering::Acquire) & READ == 0)
+                    {
+                        core::hint::spin_loop();
+                    }
+                    // `push` always links the next block before marking this
+                    // slot WRITE, so by the time we've observed WRITE (above)
+                    // and won DESTROY, `next` is guaranteed non-null.
+                    let next = block_ref.next.load(Ordering::Acquire);
+                    debug_assert!(!next.is_null(), "last slot's next block missing");
+                    self.head_block.store(next, Ordering::Release);
+                    self.head_index.store(0, Ordering::Release);
+                    unsafe { Block::<T>::dealloc(block) };
+                }
+            }
+
+            return Some(value);
+        }
+    }
+}
+
+impl<T> Drop for SegQueue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+        // The last (possibly partially-filled) block is never handed off by
+        // `pop`'s last-slot hand-off, so free it directly.
+        let block = *self.head_block.get_mut();
+        let block_ref = unsafe { &*block };
+        let tail_index = *self.tail_index.get_mut();
+        if block == *self.tail_block.get_mut() {
+            for slot in &block_ref.slots[..tail_index] {
+                if slot.state.load(Ordering::Relaxed) & READ == 0 {
+                    unsafe { drop_in_place_slot(slot) };
+                }
+            }
+        }
+        unsafe { Block::<T>::dealloc(block) };
+    }
+}
+
+unsafe fn drop_in_place_slot<T>(slot: &Slot<T>) {
+    core::ptr::drop_in_place((*slot.value.get()).as_mut_ptr());
+}
@@ -0,0 +1,116 @@
+//! A `no_std`-friendly, zero-allocation sibling of [`crate::ReaderWriterQueue`]
+//! for bare-metal/embedded use: capacity is fixed at compile time via `N`,
+//! storage is inline, and nothing here touches `core::alloc` or a linked
+//! block list. Only uses `core::*`, so it builds and is tested under `std`
+//! the same as the rest of the crate.
+
+use core::cell::UnsafeCell;
+use core::mem::{size_of, MaybeUninit};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+const CACHE_LINE_SIZE: usize = 64;
+
+/// A fixed-capacity SPSC ring buffer stored inline as `[MaybeUninit<T>; N]`.
+/// `N` must be a power of two (so the existing `size_mask` trick applies) and
+/// at least 2, the same constraints `ReaderWriterQueue::with_size` places on
+/// its block sizes.
+pub struct StaticReaderWriterQueue<T, const N: usize> {
+    data: UnsafeCell<[MaybeUninit<T>; N]>,
+    /// (Atomic) Elements are dequeued from this index.
+    front: AtomicUsize,
+    cacheline_filler0: [u8; CACHE_LINE_SIZE - size_of::<AtomicUsize>()],
+    /// (Atomic) Elements are enqueued to this index.
+    tail: AtomicUsize,
+    cacheline_filler1: [u8; CACHE_LINE_SIZE - size_of::<AtomicUsize>()],
+    size_mask: usize,
+}
+
+unsafe impl<T: Send, const N: usize> Sync for StaticReaderWriterQueue<T, N> {}
+
+impl<T, const N: usize> StaticReaderWriterQueue<T, N> {
+    pub const fn new() -> Self {
+        assert!(N.is_power_of_two(), "N must be a power of 2");
+        assert!(N >= 2, "N must be at least 2");
+        Self {
+            data: UnsafeCell::new(unsafe { MaybeUninit::uninit().assume_init() }),
+            front: AtomicUsize::new(0),
+            cacheline_filler0: [0; CACHE_LINE_SIZE - size_of::<AtomicUsize>()],
+            tail: AtomicUsize::new(0),
+            cacheline_filler1: [0; CACHE_LINE_SIZE - size_of::<AtomicUsize>()],
+            size_mask: N - 1,
+        }
+    }
+
+    /// Splits the queue into a `Producer`/`Consumer` pair so the two ends can be
+    /// moved to different threads or interrupt contexts without aliasing
+    /// `&mut self`. Requires a `'static` queue (e.g. one placed in a `static`),
+    /// since there is no allocator here to hand out a shared heap box.
+    pub fn split(&'static self) -> (Producer<'static, T, N>, Consumer<'static, T, N>) {
+        (Producer { queue: self }, Consumer { queue: self })
+    }
+
+    fn try_enqueue_inner(&self, value: T) -> Result<(), T> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let front = self.front.load(Ordering::Acquire);
+        let next_tail = (tail + 1) & self.size_mask;
+        if next_tail == front {
+            return Err(value);
+        }
+        unsafe {
+            (*self.data.get())[tail].as_mut_ptr().write(value);
+        }
+        self.tail.store(next_tail, Ordering::Release);
+        Ok(())
+    }
+
+    fn try_dequeue_inner(&self) -> Option<T> {
+        let front = self.front.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if front == tail {
+            return None;
+        }
+        let value = unsafe { (*self.data.get())[front].as_ptr().read() };
+        self.front.store((front + 1) & self.size_mask, Ordering::Release);
+        Some(value)
+    }
+}
+
+impl<T, const N: usize> Drop for StaticReaderWriterQueue<T, N> {
+    fn drop(&mut self) {
+        let front = self.front.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Relaxed);
+        let mut i = front;
+        while i != tail {
+            unsafe { (*self.data.get())[i].as_mut_ptr().drop_in_place() };
+            i = (i + 1) & self.size_mask;
+        }
+    }
+}
+
+/// The enqueue half of a [`StaticReaderWriterQueue`], obtained from
+/// [`split`](StaticReaderWriterQueue::split).
+pub struct Producer<'a, T, const N: usize> {
+    queue: &'a StaticReaderWriterQueue<T, N>,
+}
+
+unsafe impl<'a, T: Send, const N: usize> Send for Producer<'a, T, N> {}
+
+impl<'a, T, const N: usize> Producer<'a, T, N> {
+    pub fn try_enqueue(&self, value: T) -> Result<(), T> {
+        self.queue.try_enqueue_inner(value)
+    }
+}
+
+/// The dequeue half of a [`StaticReaderWriterQueue`], obtained from
+/// [`split`](StaticReaderWriterQueue::split).
+pub struct Consumer<'a, T, const N: usize> {
+    queue: &'a StaticReaderWriterQueue<T, N>,
+}
+
+unsafe impl<'a, T: Send, const N: usize> Send for Consumer<'a, T, N> {}
+
+impl<'a, T, const N: usize> Consumer<'a, T, N> {
+    pub fn try_dequeue(&self) -> Option<T> {
+        self.queue.try_dequeue_inner()
+    }
+}
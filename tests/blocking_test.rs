@@ -0,0 +1,32 @@
+use readerwriterqueue::BlockingReaderWriterQueue;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn wait_enqueue_wait_dequeue_round_trips_values() {
+    let q = Arc::new(BlockingReaderWriterQueue::<usize>::new());
+    let (producer, consumer) = q.split();
+
+    let writer = thread::spawn(move || {
+        for i in 0..10_000 {
+            producer.wait_enqueue(i).unwrap();
+        }
+        producer.close();
+    });
+
+    let mut received = Vec::new();
+    while let Some(value) = consumer.wait_dequeue() {
+        received.push(value);
+    }
+
+    writer.join().unwrap();
+    assert_eq!(received, (0..10_000).collect::<Vec<_>>());
+}
+
+#[test]
+#[should_panic(expected = "producer half already taken")]
+fn take_producer_twice_panics() {
+    let q = Arc::new(BlockingReaderWriterQueue::<usize>::new());
+    let _first = q.take_producer();
+    let _second = q.take_producer();
+}
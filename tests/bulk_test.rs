@@ -0,0 +1,18 @@
+use readerwriterqueue::ReaderWriterQueue;
+
+#[test]
+fn enqueue_bulk_try_dequeue_bulk_round_trip_across_block_boundaries() {
+    let mut q = ReaderWriterQueue::<usize>::with_size(4);
+
+    q.enqueue_bulk(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+    let mut dst = [0usize; 6];
+    let filled = q.try_dequeue_bulk(&mut dst);
+    assert_eq!(filled, 6);
+    assert_eq!(dst, [1, 2, 3, 4, 5, 6]);
+
+    let mut dst = [0usize; 10];
+    let filled = q.try_dequeue_bulk(&mut dst);
+    assert_eq!(filled, 4);
+    assert_eq!(&dst[..4], &[7, 8, 9, 10]);
+}
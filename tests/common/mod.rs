@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use std::thread;
+
+/// Runs `producers` threads each pushing `per_producer` distinct `usize`s
+/// through `push`, and `consumers` threads draining them through `pop`, then
+/// returns every popped value sorted. Shared by the MPMC stress tests for
+/// [`ConcurrentQueue`](readerwriterqueue::ConcurrentQueue) and
+/// [`SegQueue`](readerwriterqueue::SegQueue), which differ only in how a
+/// single push/pop is expressed.
+pub fn mpmc_stress<Q, Push, Pop>(
+    queue: Arc<Q>,
+    producers: usize,
+    consumers: usize,
+    per_producer: usize,
+    push: Push,
+    pop: Pop,
+) -> Vec<usize>
+where
+    Q: Send + Sync + 'static,
+    Push: Fn(&Q, usize) + Send + Sync + 'static,
+    Pop: Fn(&Q) -> Option<usize> + Send + Sync + 'static,
+{
+    let push = Arc::new(push);
+    let pop = Arc::new(pop);
+
+    let producer_threads: Vec<_> = (0..producers)
+        .map(|p| {
+            let queue = queue.clone();
+            let push = push.clone();
+            thread::spawn(move || {
+                for i in 0..per_producer {
+                    push(&queue, p * per_producer + i);
+                }
+            })
+        })
+        .collect();
+
+    let consumer_threads: Vec<_> = (0..consumers)
+        .map(|_| {
+            let queue = queue.clone();
+            let pop = pop.clone();
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                let mut misses = 0;
+                loop {
+                    match pop(&queue) {
+                        Some(value) => {
+                            popped.push(value);
+                            misses = 0;
+                        }
+                        None => {
+                            misses += 1;
+                            if misses > 1_000_000 {
+                                break;
+                            }
+                            thread::yield_now();
+                        }
+                    }
+                }
+                popped
+            })
+        })
+        .collect();
+
+    for p in producer_threads {
+        p.join().unwrap();
+    }
+
+    let mut all: Vec<usize> = consumer_threads.into_iter().flat_map(|c| c.join().unwrap()).collect();
+    all.sort_unstable();
+    all
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use readerwriterqueue::ConcurrentQueue;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn push_pop_round_trip_and_full_queue() {
+    let q = ConcurrentQueue::<usize>::new(2);
+    assert_eq!(q.push(1), Ok(()));
+    assert_eq!(q.push(2), Ok(()));
+    assert_eq!(q.push(3), Err(3));
+
+    assert_eq!(q.pop(), Some(1));
+    assert_eq!(q.pop(), Some(2));
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn mpmc_stress_every_pushed_value_is_popped_exactly_once() {
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const PER_PRODUCER: usize = 10_000;
+
+    let q = Arc::new(ConcurrentQueue::<usize>::new(64));
+    let all = common::mpmc_stress(
+        q,
+        PRODUCERS,
+        CONSUMERS,
+        PER_PRODUCER,
+        |q, mut value| {
+            while let Err(v) = q.push(value) {
+                value = v;
+                thread::yield_now();
+            }
+        },
+        |q| q.pop(),
+    );
+
+    let expected: Vec<usize> = (0..PRODUCERS * PER_PRODUCER).collect();
+    assert_eq!(all, expected);
+}
@@ -0,0 +1,41 @@
+use readerwriterqueue::ReaderWriterQueue;
+
+#[test]
+fn push_overwrite_evicts_oldest_once_ring_is_full() {
+    let mut q = ReaderWriterQueue::<usize>::ring(4);
+
+    assert_eq!(q.push_overwrite(1), None);
+    assert_eq!(q.push_overwrite(2), None);
+    assert_eq!(q.push_overwrite(3), None);
+    assert_eq!(q.push_overwrite(4), None);
+    // Ring is now full; pushing again evicts the oldest element.
+    assert_eq!(q.push_overwrite(5), Some(1));
+
+    assert_eq!(q.try_dequeue(), Some(2));
+    assert_eq!(q.try_dequeue(), Some(3));
+    assert_eq!(q.try_dequeue(), Some(4));
+    assert_eq!(q.try_dequeue(), Some(5));
+    assert_eq!(q.try_dequeue(), None);
+}
+
+#[test]
+#[should_panic(expected = "enqueue() would grow a ring queue")]
+fn enqueue_on_a_ring_panics_instead_of_growing() {
+    let mut q = ReaderWriterQueue::<usize>::ring(2);
+    q.enqueue(1);
+}
+
+#[test]
+fn repeated_ring_alloc_free_cycles_do_not_corrupt_the_allocator() {
+    // Every ring() relies on the same make_block/free_block pair enqueue_bulk
+    // and friends do; build and tear down a bunch of differently-sized rings,
+    // some left partially filled, as a regression guard on that allocator.
+    for capacity in 1..64 {
+        let mut q = ReaderWriterQueue::<usize>::ring(capacity);
+        for i in 0..capacity * 3 {
+            q.push_overwrite(i);
+        }
+        q.try_dequeue();
+        // q is dropped here, exercising free_block on a non-empty block.
+    }
+}
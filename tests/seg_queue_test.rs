@@ -0,0 +1,30 @@
+mod common;
+
+use readerwriterqueue::SegQueue;
+use std::sync::Arc;
+
+#[test]
+fn push_pop_round_trip_across_block_boundary() {
+    let q = SegQueue::<usize>::new();
+    // A block holds 32 slots; push enough to force at least one new block link.
+    for i in 0..100 {
+        q.push(i);
+    }
+    for i in 0..100 {
+        assert_eq!(q.pop(), Some(i));
+    }
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn mpmc_stress_every_pushed_value_is_popped_exactly_once() {
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const PER_PRODUCER: usize = 10_000;
+
+    let q = Arc::new(SegQueue::<usize>::new());
+    let all = common::mpmc_stress(q, PRODUCERS, CONSUMERS, PER_PRODUCER, |q, value| q.push(value), |q| q.pop());
+
+    let expected: Vec<usize> = (0..PRODUCERS * PER_PRODUCER).collect();
+    assert_eq!(all, expected);
+}
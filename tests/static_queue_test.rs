@@ -0,0 +1,19 @@
+use readerwriterqueue::StaticReaderWriterQueue;
+
+static Q: StaticReaderWriterQueue<usize, 4> = StaticReaderWriterQueue::new();
+
+#[test]
+fn split_producer_consumer_round_trip_and_full_queue() {
+    let (producer, consumer) = Q.split();
+
+    assert!(producer.try_enqueue(1).is_ok());
+    assert!(producer.try_enqueue(2).is_ok());
+    assert!(producer.try_enqueue(3).is_ok());
+    // One slot is always left empty, so a 4-slot queue holds only 3 elements.
+    assert_eq!(producer.try_enqueue(4), Err(4));
+
+    assert_eq!(consumer.try_dequeue(), Some(1));
+    assert_eq!(consumer.try_dequeue(), Some(2));
+    assert_eq!(consumer.try_dequeue(), Some(3));
+    assert_eq!(consumer.try_dequeue(), None);
+}